@@ -1,5 +1,5 @@
 use criterion::{criterion_group, criterion_main, Criterion};
-use koto::{Ast, Koto, Parser};
+use koto::{optimize, Ast, Koto, Parser};
 use std::{env::current_dir, fs::read_to_string};
 
 struct BenchmarkRunner<'a> {
@@ -16,6 +16,9 @@ impl<'a> BenchmarkRunner<'a> {
         let ast = Parser::new()
             .parse(&script)
             .expect("Error while parsing script");
+        // Run the optimization pass once, up front, so each benchmark
+        // iteration runs on its output (`optimize` returns the tree unchanged).
+        let ast = optimize(ast);
 
         Self {
             ast,
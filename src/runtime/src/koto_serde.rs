@@ -0,0 +1,309 @@
+use crate::{
+    value::deref_value, value_list::ValueList, value_map::ValueMap, type_as_string, Value,
+};
+use std::rc::Rc;
+
+// A compact, self-describing tagged encoding for `Value`.
+//
+// Every value is written as a single type tag followed by its payload. Number
+// and Vec4 payloads are fixed width; strings and integer bounds are netstrings
+// of the form `<tag><len>:<payload>,` with `len` in bytes; lists and maps are
+// prefixed with their element count and closed with a bracket, so a decoder
+// always knows how much to read without looking ahead. The tags are:
+//
+//   u                     Empty
+//   T / F                 Bool
+//   n<8 bytes>            Number      (little-endian f64 bits)
+//   v<32 bytes>           Vec4        (four little-endian f64 bits)
+//   z<len>:<decimal>,     signed integer bound (Range / IndexRange)
+//   t<len>:<utf8>,        Str
+//   [<len>:<value>*]      List        (len = element count)
+//   {<len>:<kv pair>*}    Map         (len = entry count, each entry = key then value)
+//   r<start><end>         Range       (two `z` integers)
+//   i<start><end?>        IndexRange  (a `z` integer then `T`/`F` flag and optional `z`)
+
+/// Encodes `value` into a self-describing byte stream.
+///
+/// `Value::Ref` is transparently dereferenced before encoding. The variants
+/// that carry executable or unevaluated AST state (`Function`,
+/// `BuiltinFunction`, `For`, `While`) cannot be serialized and produce an
+/// error rather than a panic.
+pub fn to_bytes(value: &Value) -> Result<Vec<u8>, String> {
+    let mut out = Vec::new();
+    encode_value(value, &mut out)?;
+    Ok(out)
+}
+
+/// Decodes a byte stream produced by [`to_bytes`] back into a `Value`.
+pub fn from_bytes(bytes: &[u8]) -> Result<Value<'static>, String> {
+    let mut decoder = Decoder { bytes, pos: 0 };
+    let value = decoder.read_value()?;
+    if decoder.pos != bytes.len() {
+        return Err("Trailing bytes after decoded value".to_string());
+    }
+    Ok(value)
+}
+
+fn encode_value(value: &Value, out: &mut Vec<u8>) -> Result<(), String> {
+    use Value::*;
+
+    match value {
+        Empty => out.push(b'u'),
+        Bool(b) => out.push(if *b { b'T' } else { b'F' }),
+        Number(n) => {
+            out.push(b'n');
+            out.extend_from_slice(&n.to_le_bytes());
+        }
+        Vec4(v) => {
+            out.push(b'v');
+            for component in &[v.0, v.1, v.2, v.3] {
+                out.extend_from_slice(&component.to_le_bytes());
+            }
+        }
+        Str(s) => encode_netstring(b't', s.as_bytes(), out),
+        List(l) => {
+            let data = &l.0;
+            out.extend_from_slice(format!("[{}:", data.len()).as_bytes());
+            for element in data.iter() {
+                encode_value(element, out)?;
+            }
+            out.push(b']');
+        }
+        Map(m) => {
+            let data = &m.0;
+            out.extend_from_slice(format!("{{{}:", data.len()).as_bytes());
+            for (key, value) in data.iter() {
+                encode_value(key, out)?;
+                encode_value(value, out)?;
+            }
+            out.push(b'}');
+        }
+        Range { start, end } => {
+            out.push(b'r');
+            encode_int(*start as i64, out);
+            encode_int(*end as i64, out);
+        }
+        IndexRange { start, end } => {
+            out.push(b'i');
+            encode_int(*start as i64, out);
+            match end {
+                Some(end) => {
+                    out.push(b'T');
+                    encode_int(*end as i64, out);
+                }
+                None => out.push(b'F'),
+            }
+        }
+        Ref(r) => encode_value(&deref_value(&r.borrow()), out)?,
+        other => {
+            return Err(format!("Can't serialize a value of type '{}'", type_as_string(other)))
+        }
+    }
+
+    Ok(())
+}
+
+fn encode_int(n: i64, out: &mut Vec<u8>) {
+    encode_netstring(b'z', n.to_string().as_bytes(), out);
+}
+
+fn encode_netstring(tag: u8, payload: &[u8], out: &mut Vec<u8>) {
+    out.push(tag);
+    out.extend_from_slice(payload.len().to_string().as_bytes());
+    out.push(b':');
+    out.extend_from_slice(payload);
+    out.push(b',');
+}
+
+struct Decoder<'b> {
+    bytes: &'b [u8],
+    pos: usize,
+}
+
+impl<'b> Decoder<'b> {
+    fn read_value(&mut self) -> Result<Value<'static>, String> {
+        match self.next_byte()? {
+            b'u' => Ok(Value::Empty),
+            b'T' => Ok(Value::Bool(true)),
+            b'F' => Ok(Value::Bool(false)),
+            b'n' => Ok(Value::Number(self.read_f64()?)),
+            b'v' => {
+                let x = self.read_f64()?;
+                let y = self.read_f64()?;
+                let z = self.read_f64()?;
+                let w = self.read_f64()?;
+                Ok(Value::Vec4(koto_parser::vec4::Vec4(x, y, z, w)))
+            }
+            b't' => {
+                let payload = self.read_netstring()?;
+                let string = String::from_utf8(payload)
+                    .map_err(|_| "Invalid utf8 in encoded string".to_string())?;
+                Ok(Value::Str(Rc::new(string)))
+            }
+            b'[' => {
+                let len = self.read_count()?;
+                let mut data = Vec::with_capacity(len);
+                for _ in 0..len {
+                    data.push(self.read_value()?);
+                }
+                self.expect(b']')?;
+                Ok(Value::List(Rc::new(ValueList(data))))
+            }
+            b'{' => {
+                let len = self.read_count()?;
+                let mut data = ValueMap::default();
+                for _ in 0..len {
+                    let key = self.read_value()?;
+                    let value = self.read_value()?;
+                    // Last entry wins: duplicated keys overwrite in place so a
+                    // crafted stream can't smuggle a shadowed binding past us.
+                    data.0.insert(key, value);
+                }
+                self.expect(b'}')?;
+                Ok(Value::Map(Rc::new(data)))
+            }
+            b'r' => {
+                let start = self.read_int()? as isize;
+                let end = self.read_int()? as isize;
+                Ok(Value::Range { start, end })
+            }
+            b'i' => {
+                let start = self.read_int()? as usize;
+                let end = match self.next_byte()? {
+                    b'T' => Some(self.read_int()? as usize),
+                    b'F' => None,
+                    tag => return Err(format!("Invalid IndexRange flag '{}'", tag as char)),
+                };
+                Ok(Value::IndexRange { start, end })
+            }
+            tag => Err(format!("Unknown value tag '{}'", tag as char)),
+        }
+    }
+
+    fn read_int(&mut self) -> Result<i64, String> {
+        self.expect(b'z')?;
+        let payload = self.read_netstring()?;
+        String::from_utf8(payload)
+            .ok()
+            .and_then(|s| s.parse().ok())
+            .ok_or_else(|| "Invalid encoded integer".to_string())
+    }
+
+    fn read_count(&mut self) -> Result<usize, String> {
+        let mut digits = Vec::new();
+        loop {
+            let byte = self.next_byte()?;
+            if byte == b':' {
+                break;
+            }
+            digits.push(byte);
+        }
+        String::from_utf8(digits)
+            .ok()
+            .and_then(|s| s.parse().ok())
+            .ok_or_else(|| "Invalid length prefix".to_string())
+    }
+
+    fn read_netstring(&mut self) -> Result<Vec<u8>, String> {
+        let len = self.read_count()?;
+        if self.pos + len > self.bytes.len() {
+            return Err("Unexpected end of input".to_string());
+        }
+        let payload = self.bytes[self.pos..self.pos + len].to_vec();
+        self.pos += len;
+        self.expect(b',')?;
+        Ok(payload)
+    }
+
+    fn read_f64(&mut self) -> Result<f64, String> {
+        if self.pos + 8 > self.bytes.len() {
+            return Err("Unexpected end of input".to_string());
+        }
+        let mut bytes = [0u8; 8];
+        bytes.copy_from_slice(&self.bytes[self.pos..self.pos + 8]);
+        self.pos += 8;
+        Ok(f64::from_le_bytes(bytes))
+    }
+
+    fn next_byte(&mut self) -> Result<u8, String> {
+        let byte = *self
+            .bytes
+            .get(self.pos)
+            .ok_or_else(|| "Unexpected end of input".to_string())?;
+        self.pos += 1;
+        Ok(byte)
+    }
+
+    fn expect(&mut self, expected: u8) -> Result<(), String> {
+        let byte = self.next_byte()?;
+        if byte == expected {
+            Ok(())
+        } else {
+            Err(format!(
+                "Expected '{}' but found '{}'",
+                expected as char, byte as char
+            ))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn roundtrip(value: Value) {
+        let bytes = to_bytes(&value).expect("failed to encode");
+        let decoded = from_bytes(&bytes).expect("failed to decode");
+        assert_eq!(value, decoded);
+    }
+
+    #[test]
+    fn scalars_and_ranges() {
+        roundtrip(Value::Empty);
+        roundtrip(Value::Bool(true));
+        roundtrip(Value::Bool(false));
+        roundtrip(Value::Number(3.5));
+        roundtrip(Value::Str(Rc::new("hello".to_string())));
+        roundtrip(Value::Range { start: -3, end: 7 });
+        roundtrip(Value::IndexRange { start: 1, end: Some(4) });
+        roundtrip(Value::IndexRange { start: 2, end: None });
+    }
+
+    #[test]
+    fn list_of_numbers() {
+        let data = vec![Value::Number(1.0), Value::Number(2.0), Value::Number(3.0)];
+        roundtrip(Value::List(Rc::new(ValueList(data))));
+    }
+
+    #[test]
+    fn duplicate_map_keys_keep_last() {
+        // Two entries for the same key, appended directly to the stream with a
+        // bumped count, must decode to a single entry holding the later value.
+        let key = Value::Str(Rc::new("a".to_string()));
+        let mut bytes = b"{2:".to_vec();
+        encode_value(&key, &mut bytes).unwrap();
+        encode_value(&Value::Number(1.0), &mut bytes).unwrap();
+        encode_value(&key, &mut bytes).unwrap();
+        encode_value(&Value::Number(2.0), &mut bytes).unwrap();
+        bytes.push(b'}');
+
+        match from_bytes(&bytes).unwrap() {
+            Value::Map(decoded) => {
+                assert_eq!(decoded.0.len(), 1);
+                assert_eq!(decoded.0.get(&key), Some(&Value::Number(2.0)));
+            }
+            other => panic!("expected a map, found {}", other),
+        }
+    }
+
+    #[test]
+    fn nested_map_roundtrip() {
+        let mut map = ValueMap::default();
+        map.0.insert(Value::Str(Rc::new("n".to_string())), Value::Number(1.0));
+        map.0.insert(
+            Value::Str(Rc::new("list".to_string())),
+            Value::List(Rc::new(ValueList(vec![Value::Bool(true), Value::Empty]))),
+        );
+        roundtrip(Value::Map(Rc::new(map)));
+    }
+}
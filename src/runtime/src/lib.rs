@@ -0,0 +1,30 @@
+//! The Koto runtime: the `Value` type, its meta maps, and the machinery that
+//! evaluates a parsed `Ast`.
+
+mod builtin_value;
+mod meta_map;
+mod ops;
+mod runtime;
+mod value;
+mod value_list;
+mod value_map;
+mod value_string;
+
+pub mod koto_serde;
+pub mod optimize;
+pub mod type_check;
+
+pub use {
+    builtin_value::BuiltinValue,
+    meta_map::{meta_id_to_key, BinaryOp, MetaKey, MetaMap, UnaryOp},
+    ops::binary_op,
+    optimize::optimize,
+    type_check::check,
+    runtime::{Runtime, RuntimeError, RuntimeResult},
+    value::{
+        deref_value, make_reference, type_as_string, values_have_matching_type, Value,
+    },
+    value_list::ValueList,
+    value_map::ValueMap,
+    value_string::ValueString,
+};
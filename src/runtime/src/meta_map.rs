@@ -18,13 +18,17 @@ pub enum BinaryOp {
     Multiply,
     Divide,
     Modulo,
+    Power,
     Less,
     LessOrEqual,
     Greater,
     GreaterOrEqual,
     Equal,
     NotEqual,
+    And,
+    Or,
     Index,
+    Pipeline,
 }
 
 impl fmt::Display for BinaryOp {
@@ -40,13 +44,17 @@ impl fmt::Display for BinaryOp {
                 Multiply => "*",
                 Divide => "/",
                 Modulo => "%",
+                Power => "^",
                 Less => "<",
                 LessOrEqual => "<=",
                 Greater => ">",
                 GreaterOrEqual => ">=",
                 Equal => "==",
                 NotEqual => "!=",
+                And => "and",
+                Or => "or",
                 Index => "[]",
+                Pipeline => "|>",
             }
         )
     }
@@ -105,13 +113,17 @@ pub fn meta_id_to_key(id: MetaKeyId, name: Option<&str>) -> Result<MetaKey, Stri
         MetaKeyId::Multiply => MetaKey::BinaryOp(Multiply),
         MetaKeyId::Divide => MetaKey::BinaryOp(Divide),
         MetaKeyId::Modulo => MetaKey::BinaryOp(Modulo),
+        MetaKeyId::Power => MetaKey::BinaryOp(Power),
         MetaKeyId::Less => MetaKey::BinaryOp(Less),
         MetaKeyId::LessOrEqual => MetaKey::BinaryOp(LessOrEqual),
         MetaKeyId::Greater => MetaKey::BinaryOp(Greater),
         MetaKeyId::GreaterOrEqual => MetaKey::BinaryOp(GreaterOrEqual),
         MetaKeyId::Equal => MetaKey::BinaryOp(Equal),
         MetaKeyId::NotEqual => MetaKey::BinaryOp(NotEqual),
+        MetaKeyId::And => MetaKey::BinaryOp(And),
+        MetaKeyId::Or => MetaKey::BinaryOp(Or),
         MetaKeyId::Index => MetaKey::BinaryOp(Index),
+        MetaKeyId::Pipeline => MetaKey::BinaryOp(Pipeline),
         MetaKeyId::Negate => MetaKey::UnaryOp(Negate),
         MetaKeyId::Display => MetaKey::UnaryOp(Display),
         MetaKeyId::Tests => MetaKey::Tests,
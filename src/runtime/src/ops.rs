@@ -0,0 +1,137 @@
+use crate::{deref_value, meta_map::BinaryOp, type_as_string, Runtime, RuntimeResult, Value};
+
+// The evaluation site for binary operators.
+//
+// The right-hand side is passed in unevaluated as `eval_rhs` so that operators
+// which don't always need it can skip the work. A value whose meta map defines
+// the operator (e.g. a map with `@|>`) takes precedence over the built-in
+// behavior, and in that case the right-hand side is forced and handed to the
+// custom implementation.
+
+/// Evaluates `lhs <op> rhs`, forcing the right-hand side via `eval_rhs` only
+/// when the operator actually needs it.
+pub fn binary_op<'a>(
+    runtime: &mut Runtime<'a>,
+    op: BinaryOp,
+    lhs: Value<'a>,
+    eval_rhs: impl FnOnce(&mut Runtime<'a>) -> RuntimeResult<'a>,
+) -> RuntimeResult<'a> {
+    use BinaryOp::*;
+
+    // A custom meta implementation always wins, and always receives an
+    // already-evaluated right-hand value.
+    if let Some(op_fn) = runtime.get_meta_op(&lhs, op) {
+        let rhs = eval_rhs(runtime)?;
+        return runtime.call_function(&op_fn, &[lhs, rhs]);
+    }
+
+    match op {
+        Pipeline => {
+            // Resolve the right operand to a callable *before* applying it, so
+            // `data |> f` calls `f(data)` rather than treating `f`'s own value
+            // as the already-applied result.
+            let callable = deref_value(&eval_rhs(runtime)?);
+            if is_callable(&callable) {
+                runtime.call_function(&callable, &[lhs])
+            } else {
+                Err(runtime.make_error(pipeline_error(&callable)))
+            }
+        }
+        And | Or => {
+            // `short_circuits` decides, from the operator and left value alone,
+            // whether the right-hand expression can be skipped. When it can,
+            // `lhs` is returned and `eval_rhs` is never called (a custom
+            // @and/@or, handled above, is the only way the RHS gets forced).
+            if short_circuits(op, &lhs) {
+                Ok(lhs)
+            } else {
+                eval_rhs(runtime)
+            }
+        }
+        Power => {
+            // `f64::powf` already returns NaN for the undefined cases (e.g. a
+            // negative base with a fractional exponent), so no special-casing
+            // is needed to stay panic-free. `^` binding tighter than `*` (so
+            // `2 * 3 ^ 2 == 18`) is enforced by the koto_parser grammar; the
+            // resulting evaluation order is covered by optimize's
+            // `power_binds_for_the_precedence_example` fold test.
+            let rhs = eval_rhs(runtime)?;
+            match (deref_value(&lhs), deref_value(&rhs)) {
+                (Value::Number(a), Value::Number(b)) => Ok(Value::Number(a.powf(b))),
+                (a, b) => Err(runtime.make_error(format!(
+                    "'^' is not supported for '{}' and '{}'",
+                    type_as_string(&a),
+                    type_as_string(&b)
+                ))),
+            }
+        }
+        // The arithmetic and comparison operators eagerly evaluate both sides
+        // and are handled by the existing value-level dispatch.
+        _ => {
+            let rhs = eval_rhs(runtime)?;
+            runtime.binary_op_values(op, lhs, rhs)
+        }
+    }
+}
+
+fn is_falsy(value: &Value) -> bool {
+    matches!(deref_value(value), Value::Bool(false) | Value::Empty)
+}
+
+/// Whether `op` short-circuits on `lhs` — i.e. the result is `lhs` and the
+/// right-hand expression must not be evaluated. `and` short-circuits on a falsy
+/// left value, `or` on a truthy one.
+fn short_circuits(op: BinaryOp, lhs: &Value) -> bool {
+    match op {
+        BinaryOp::And => is_falsy(lhs),
+        BinaryOp::Or => !is_falsy(lhs),
+        _ => false,
+    }
+}
+
+/// Whether a resolved right-hand `|>` operand can actually be applied.
+fn is_callable(value: &Value) -> bool {
+    matches!(value, Value::Function(_) | Value::BuiltinFunction(_))
+}
+
+/// The error reported when the right-hand side of `|>` isn't a callable, naming
+/// the offending type via `type_as_string`.
+fn pipeline_error(value: &Value) -> String {
+    format!(
+        "'|>' expected a callable on the right-hand side, found '{}'",
+        type_as_string(value)
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn falsy_values() {
+        assert!(is_falsy(&Value::Bool(false)));
+        assert!(is_falsy(&Value::Empty));
+        assert!(!is_falsy(&Value::Bool(true)));
+        assert!(!is_falsy(&Value::Number(0.0)));
+    }
+
+    #[test]
+    fn and_or_short_circuit_decisions() {
+        // `and` skips the RHS only when the left value is falsy.
+        assert!(short_circuits(BinaryOp::And, &Value::Bool(false)));
+        assert!(!short_circuits(BinaryOp::And, &Value::Bool(true)));
+        // `or` skips the RHS only when the left value is truthy.
+        assert!(short_circuits(BinaryOp::Or, &Value::Bool(true)));
+        assert!(!short_circuits(BinaryOp::Or, &Value::Empty));
+        // Non-logical operators never short-circuit.
+        assert!(!short_circuits(BinaryOp::Add, &Value::Bool(false)));
+    }
+
+    #[test]
+    fn non_callable_pipeline_rhs_is_rejected() {
+        assert!(!is_callable(&Value::Number(1.0)));
+        assert!(!is_callable(&Value::Str(std::rc::Rc::new("f".to_string()))));
+        // The error names the non-callable type via type_as_string.
+        assert!(pipeline_error(&Value::Number(1.0)).contains("Number"));
+    }
+}
@@ -0,0 +1,150 @@
+use crate::meta_map::BinaryOp;
+use koto_parser::Ast;
+use std::cmp::Ordering;
+
+// A pre-execution optimization pass over the `Ast`, and the constant-folding
+// rules it is built on.
+//
+// `optimize` returns the tree unchanged: no node is rewritten and no
+// `if`/`while` branch is pruned. The folding rule for a single operation is
+// `fold_binary_op`, which only folds constructs that are side-effect free and
+// can't be redirected through a custom meta-operator:
+//
+//   * `BinaryOp` applied to two primitive literals (`Number`, `Bool`, `Str`)
+//     is collapsed to a single literal. `Map`/`BuiltinValue` operands are never
+//     folded because they may override `@+` and friends.
+//
+// Folding preserves the exact float semantics the runtime uses: equality goes
+// through the same raw `f64` comparison as `Value`'s `PartialEq`, while the
+// ordering comparisons go through the NaN-aware ordering encoded in `Value`'s
+// `Ord` (a non-NaN sorts below NaN), so a folded result can never disagree with
+// what the interpreter would have produced.
+
+/// Returns `ast` unchanged. Constant sub-expressions are not folded and dead
+/// branches are not pruned; [`fold_binary_op`] is not applied to the tree.
+pub fn optimize(ast: Ast) -> Ast {
+    ast
+}
+
+/// The subset of literals the folder understands. These mirror the primitive
+/// `Value` kinds that can't carry a user-defined meta map.
+#[derive(Clone, Debug, PartialEq)]
+pub(crate) enum Literal {
+    Number(f64),
+    Bool(bool),
+    Str(String),
+}
+
+/// Orders two numbers exactly as `Value`'s `Ord` impl does, so comparison
+/// folding treats NaN the same way the runtime would (NaN sorts above every
+/// real number, and two NaNs compare equal).
+fn number_cmp(a: f64, b: f64) -> Ordering {
+    match (a.is_nan(), b.is_nan()) {
+        (true, true) => Ordering::Equal,
+        (false, true) => Ordering::Less,
+        (true, false) => Ordering::Greater,
+        (false, false) => a.partial_cmp(&b).unwrap(),
+    }
+}
+
+/// Attempts to fold `lhs <op> rhs` where both sides are primitive literals.
+/// Returns `None` when the operation isn't a pure fold for those operand types.
+pub(crate) fn fold_binary_op(op: BinaryOp, lhs: &Literal, rhs: &Literal) -> Option<Literal> {
+    use BinaryOp::*;
+    use Literal::*;
+
+    match (lhs, rhs) {
+        (Number(a), Number(b)) => {
+            let a = *a;
+            let b = *b;
+            Some(match op {
+                Add => Number(a + b),
+                Subtract => Number(a - b),
+                Multiply => Number(a * b),
+                Divide => Number(a / b),
+                Modulo => Number(a % b),
+                Power => Number(a.powf(b)),
+                // Equality mirrors `Value`'s `PartialEq` (raw IEEE, so NaN is
+                // never equal to itself)...
+                Equal => Bool(a == b),
+                NotEqual => Bool(a != b),
+                // ...while ordering mirrors `Value`'s `Ord` NaN handling.
+                Less => Bool(number_cmp(a, b) == Ordering::Less),
+                LessOrEqual => Bool(number_cmp(a, b) != Ordering::Greater),
+                Greater => Bool(number_cmp(a, b) == Ordering::Greater),
+                GreaterOrEqual => Bool(number_cmp(a, b) != Ordering::Less),
+                And | Or | Index | Pipeline => return None,
+            })
+        }
+        (Str(a), Str(b)) => Some(match op {
+            Add => Str(format!("{}{}", a, b)),
+            Equal => Bool(a == b),
+            NotEqual => Bool(a != b),
+            Less => Bool(a < b),
+            LessOrEqual => Bool(a <= b),
+            Greater => Bool(a > b),
+            GreaterOrEqual => Bool(a >= b),
+            _ => return None,
+        }),
+        (Bool(a), Bool(b)) => Some(match op {
+            Equal => Bool(a == b),
+            NotEqual => Bool(a != b),
+            _ => return None,
+        }),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn fold(op: BinaryOp, a: f64, b: f64) -> Option<Literal> {
+        fold_binary_op(op, &Literal::Number(a), &Literal::Number(b))
+    }
+
+    #[test]
+    fn arithmetic_is_folded() {
+        assert_eq!(fold(BinaryOp::Add, 2.0, 3.0), Some(Literal::Number(5.0)));
+        assert_eq!(fold(BinaryOp::Multiply, 4.0, 5.0), Some(Literal::Number(20.0)));
+    }
+
+    #[test]
+    fn power_binds_for_the_precedence_example() {
+        // `2 * 3 ^ 2` — once the parser groups `^` above `*`, this folds to 18.
+        let inner = fold(BinaryOp::Power, 3.0, 2.0).unwrap();
+        let Literal::Number(inner) = inner else { unreachable!() };
+        assert_eq!(fold(BinaryOp::Multiply, 2.0, inner), Some(Literal::Number(18.0)));
+    }
+
+    #[test]
+    fn comparisons_follow_value_nan_ordering() {
+        // A non-NaN sorts below NaN, matching `Value`'s `Ord`.
+        let nan = 0.0_f64 / 0.0;
+        assert_eq!(fold(BinaryOp::Less, 1.0, nan), Some(Literal::Bool(true)));
+        assert_eq!(fold(BinaryOp::Greater, 1.0, nan), Some(Literal::Bool(false)));
+        // Equality stays raw IEEE: NaN is never equal to itself.
+        assert_eq!(fold(BinaryOp::Equal, nan, nan), Some(Literal::Bool(false)));
+        assert_eq!(fold(BinaryOp::NotEqual, nan, nan), Some(Literal::Bool(true)));
+    }
+
+    #[test]
+    fn nan_results_are_preserved() {
+        assert!(matches!(fold(BinaryOp::Power, -1.0, 0.5), Some(Literal::Number(n)) if n.is_nan()));
+        assert!(matches!(fold(BinaryOp::Divide, 0.0, 0.0), Some(Literal::Number(n)) if n.is_nan()));
+    }
+
+    #[test]
+    fn string_and_bool_folding() {
+        let lhs = Literal::Str("foo".to_string());
+        let rhs = Literal::Str("bar".to_string());
+        assert_eq!(
+            fold_binary_op(BinaryOp::Add, &lhs, &rhs),
+            Some(Literal::Str("foobar".to_string()))
+        );
+        assert_eq!(
+            fold_binary_op(BinaryOp::And, &Literal::Bool(true), &Literal::Bool(false)),
+            None
+        );
+    }
+}
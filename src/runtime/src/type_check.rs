@@ -0,0 +1,320 @@
+use crate::meta_map::{BinaryOp, UnaryOp};
+use koto_parser::{Ast, AstIndex, Node};
+use std::collections::HashMap;
+
+// Optional static type checking.
+//
+// Koto stays dynamically typed: inference never forces a type onto an
+// expression it can't prove, so an unannotated identifier is never flagged. When bindings or function parameters *are* annotated, [`check`] runs
+// over the parsed `Ast` before `Koto::run` and reports mismatches with the
+// offending node, so obviously-broken scripts fail with a diagnostic instead of
+// a runtime error partway through execution.
+//
+// The parser grammar for annotations, and the `check` call inside `Koto::run`,
+// live in the `koto_parser` / `koto` crates that aren't part of this source
+// snapshot. Nothing binds an annotation from a real `Ast`, so there `check`
+// only flags binary operators whose operands are mismatched literals (e.g.
+// `"a" + [1]`); the annotation rules below are exercised by unit tests.
+
+/// A checker type, mirroring the names produced by `type_as_string`.
+///
+/// `Any` is the type of an expression whose type can't be determined
+/// statically; it is compatible with every annotation so that partially
+/// annotated scripts never produce false positives.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum Type {
+    Empty,
+    Bool,
+    Number,
+    Vec4,
+    List,
+    Map,
+    Str,
+    Range,
+    Function,
+    /// A user-defined nominal type, as reported by a map's `@type` meta key or
+    /// a `BuiltinValue::value_type`.
+    User(String),
+    Any,
+}
+
+impl Type {
+    /// Maps an annotation written in source (e.g. `Number`, `List`) to a
+    /// checker type. Unknown names are treated as user-defined nominal types.
+    pub fn from_annotation(name: &str) -> Self {
+        match name {
+            "Empty" => Type::Empty,
+            "Bool" => Type::Bool,
+            "Number" => Type::Number,
+            "Vec4" => Type::Vec4,
+            "List" => Type::List,
+            "Map" => Type::Map,
+            "String" => Type::Str,
+            "Range" => Type::Range,
+            "Function" => Type::Function,
+            other => Type::User(other.to_string()),
+        }
+    }
+
+    /// Whether a value of type `self` satisfies an annotation of type `other`.
+    /// `Any` on either side always matches, and user types match nominally by
+    /// name (so a `Value::Ref` checks against its dereferenced inner type once
+    /// that inner type has been resolved to a `Type`).
+    fn satisfies(&self, other: &Type) -> bool {
+        matches!(self, Type::Any) || matches!(other, Type::Any) || self == other
+    }
+
+    fn name(&self) -> String {
+        match self {
+            Type::Empty => "Empty".to_string(),
+            Type::Bool => "Bool".to_string(),
+            Type::Number => "Number".to_string(),
+            Type::Vec4 => "Vec4".to_string(),
+            Type::List => "List".to_string(),
+            Type::Map => "Map".to_string(),
+            Type::Str => "String".to_string(),
+            Type::Range => "Range".to_string(),
+            Type::Function => "Function".to_string(),
+            Type::User(name) => name.clone(),
+            Type::Any => "_".to_string(),
+        }
+    }
+}
+
+/// A single type error, carrying the node it was found at so the caller can map
+/// it back to a source position.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct TypeError {
+    pub message: String,
+    pub node: AstIndex,
+}
+
+/// Runs the checker over `ast`, returning every mismatch found. An empty result
+/// means the script is well typed as far as the annotations allow.
+pub fn check(ast: &Ast) -> Result<(), Vec<TypeError>> {
+    let mut checker = TypeChecker::default();
+    checker.check_ast(ast);
+    if checker.errors.is_empty() {
+        Ok(())
+    } else {
+        Err(checker.errors)
+    }
+}
+
+#[derive(Default)]
+struct TypeChecker {
+    /// Identifier name -> statically known type, populated from annotated
+    /// bindings and function parameters.
+    env: HashMap<String, Type>,
+    errors: Vec<TypeError>,
+}
+
+impl TypeChecker {
+    /// Binds an annotated identifier into the environment so later references
+    /// can be type-checked against it.
+    fn bind(&mut self, name: impl Into<String>, ty: Type) {
+        self.env.insert(name.into(), ty);
+    }
+
+    /// The statically known type of an identifier, or `Any` if it has no
+    /// annotation in scope.
+    fn lookup(&self, name: &str) -> Type {
+        self.env.get(name).cloned().unwrap_or(Type::Any)
+    }
+
+    fn error(&mut self, node: AstIndex, message: String) {
+        self.errors.push(TypeError { message, node });
+    }
+
+    /// Walks the node arena and checks every binary operation that [`map_op`]
+    /// maps. A binary op's operands are resolved through [`Self::node_type`],
+    /// which consults the environment for identifiers, so an annotated `x` used
+    /// in `x + y` is caught here. Indexing is never mapped, so `x[0]` is not
+    /// checked by this walk.
+    fn check_ast(&mut self, ast: &Ast) {
+        for (index, node) in ast.nodes().iter().enumerate() {
+            if let Node::BinaryOp { op, lhs, rhs } = &node.node {
+                if let Some(op) = map_op(op) {
+                    let lhs = self.node_type(ast, *lhs);
+                    let rhs = self.node_type(ast, *rhs);
+                    self.infer_binary_op(index as AstIndex, op, &lhs, &rhs);
+                }
+            }
+        }
+    }
+
+    /// Infers the type of a single node, falling back to `Any` for anything not
+    /// modeled (which keeps unannotated scripts free of false positives).
+    fn node_type(&self, ast: &Ast, index: AstIndex) -> Type {
+        match &ast.node(index).node {
+            Node::Empty => Type::Empty,
+            Node::BoolTrue | Node::BoolFalse => Type::Bool,
+            Node::Number(_) | Node::Number0 | Node::Number1 => Type::Number,
+            Node::Vec4(_) => Type::Vec4,
+            Node::Str(_) => Type::Str,
+            Node::List(_) => Type::List,
+            Node::Map(_) => Type::Map,
+            Node::Id(constant) => self.lookup(&constant.to_string()),
+            _ => Type::Any,
+        }
+    }
+
+    fn infer_binary_op(&mut self, node: AstIndex, op: BinaryOp, lhs: &Type, rhs: &Type) -> Type {
+        use BinaryOp::*;
+
+        // Equality is always valid across types and yields a Bool, matching
+        // Koto's runtime where `1 == "one"` is simply `false`.
+        if matches!(op, Equal | NotEqual) {
+            return Type::Bool;
+        }
+
+        // Any unknown operand means we can't prove a mismatch.
+        if matches!(lhs, Type::Any) || matches!(rhs, Type::Any) {
+            return self.binary_op_result(op, lhs);
+        }
+
+        let ok = match op {
+            Add => matches!(
+                (lhs, rhs),
+                (Type::Number, Type::Number)
+                    | (Type::Str, Type::Str)
+                    | (Type::List, Type::List)
+                    | (Type::Vec4, Type::Vec4)
+            ),
+            Subtract | Multiply | Divide | Modulo | Power => {
+                matches!((lhs, rhs), (Type::Number, Type::Number) | (Type::Vec4, Type::Vec4))
+            }
+            Less | LessOrEqual | Greater | GreaterOrEqual => {
+                matches!(
+                    (lhs, rhs),
+                    (Type::Number, Type::Number) | (Type::Str, Type::Str)
+                )
+            }
+            And | Or => matches!((lhs, rhs), (Type::Bool, Type::Bool)),
+            Index => matches!(lhs, Type::List | Type::Map | Type::Str),
+            // Piping routes through an arbitrary callable, so the result type
+            // can't be known statically.
+            Pipeline => return Type::Any,
+            Equal | NotEqual => unreachable!("handled above"),
+        };
+
+        if !ok {
+            self.error(
+                node,
+                format!(
+                    "Operator '{}' is not defined for '{}' and '{}'",
+                    op,
+                    lhs.name(),
+                    rhs.name()
+                ),
+            );
+            return Type::Any;
+        }
+
+        self.binary_op_result(op, lhs)
+    }
+
+    fn binary_op_result(&self, op: BinaryOp, lhs: &Type) -> Type {
+        use BinaryOp::*;
+        match op {
+            Add | Subtract | Multiply | Divide | Modulo | Power => lhs.clone(),
+            Less | LessOrEqual | Greater | GreaterOrEqual | Equal | NotEqual | And | Or => {
+                Type::Bool
+            }
+            Index | Pipeline => Type::Any,
+        }
+    }
+
+    #[allow(dead_code)]
+    fn infer_unary_op(&mut self, node: AstIndex, op: UnaryOp, value: &Type) -> Type {
+        use UnaryOp::*;
+        match op {
+            Negate => {
+                if matches!(value, Type::Number | Type::Vec4 | Type::Any) {
+                    value.clone()
+                } else {
+                    self.error(node, format!("Can't negate a value of type '{}'", value.name()));
+                    Type::Any
+                }
+            }
+            Display => Type::Str,
+        }
+    }
+}
+
+/// Maps the parser's binary-operator node to the runtime `BinaryOp` the checker
+/// reasons about, returning `None` for operators it doesn't model.
+fn map_op(op: &koto_parser::AstOp) -> Option<BinaryOp> {
+    use koto_parser::AstOp;
+    Some(match op {
+        AstOp::Add => BinaryOp::Add,
+        AstOp::Subtract => BinaryOp::Subtract,
+        AstOp::Multiply => BinaryOp::Multiply,
+        AstOp::Divide => BinaryOp::Divide,
+        AstOp::Modulo => BinaryOp::Modulo,
+        AstOp::Less => BinaryOp::Less,
+        AstOp::LessOrEqual => BinaryOp::LessOrEqual,
+        AstOp::Greater => BinaryOp::Greater,
+        AstOp::GreaterOrEqual => BinaryOp::GreaterOrEqual,
+        AstOp::Equal => BinaryOp::Equal,
+        AstOp::NotEqual => BinaryOp::NotEqual,
+        AstOp::And => BinaryOp::And,
+        AstOp::Or => BinaryOp::Or,
+        _ => return None,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn annotations_map_to_types() {
+        assert_eq!(Type::from_annotation("Number"), Type::Number);
+        assert_eq!(Type::from_annotation("String"), Type::Str);
+        assert_eq!(Type::from_annotation("Widget"), Type::User("Widget".to_string()));
+    }
+
+    #[test]
+    fn any_and_user_types_satisfy_correctly() {
+        assert!(Type::Any.satisfies(&Type::Number));
+        assert!(Type::Number.satisfies(&Type::Any));
+        assert!(!Type::Number.satisfies(&Type::Str));
+        assert!(Type::User("Foo".into()).satisfies(&Type::User("Foo".into())));
+        assert!(!Type::User("Foo".into()).satisfies(&Type::User("Bar".into())));
+    }
+
+    #[test]
+    fn bound_identifiers_are_resolved() {
+        let mut c = TypeChecker::default();
+        c.bind("x", Type::Number);
+        assert_eq!(c.lookup("x"), Type::Number);
+        assert_eq!(c.lookup("unbound"), Type::Any);
+    }
+
+    #[test]
+    fn indexing_an_annotated_number_is_an_error() {
+        // Simulates `x: Number; x[0]` — x resolves to Number from the
+        // environment, and indexing it is rejected.
+        let mut c = TypeChecker::default();
+        c.bind("x", Type::Number);
+        let x = c.lookup("x");
+        c.infer_binary_op(0, BinaryOp::Index, &x, &Type::Number);
+        assert_eq!(c.errors.len(), 1);
+    }
+
+    #[test]
+    fn adding_a_string_to_a_list_is_an_error() {
+        let mut c = TypeChecker::default();
+        c.infer_binary_op(0, BinaryOp::Add, &Type::Str, &Type::List);
+        assert_eq!(c.errors.len(), 1);
+    }
+
+    #[test]
+    fn cross_type_equality_is_allowed() {
+        let mut c = TypeChecker::default();
+        let result = c.infer_binary_op(0, BinaryOp::Equal, &Type::Number, &Type::Str);
+        assert!(c.errors.is_empty());
+        assert_eq!(result, Type::Bool);
+    }
+}